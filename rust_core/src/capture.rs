@@ -1,6 +1,7 @@
 //! Fast screen capture
 
-use crate::detection::{find_cyan_highlight, find_yellow_arrow};
+use crate::detection::{find_cyan_highlight, find_yellow_arrow, DetectionConfig};
+use crate::detector::ObjectDetector;
 use crate::types::{DetectionResult, Point};
 use std::time::Instant;
 
@@ -31,30 +32,117 @@ pub fn capture_region(x: i32, y: i32, width: u32, height: u32) -> Result<Vec<u8>
 
 /// Capture and analyze in one optimized call
 pub fn capture_and_analyze(x: i32, y: i32, width: u32, height: u32) -> Result<DetectionResult, String> {
+    capture_and_analyze_with(x, y, width, height, &DetectionConfig::default())
+}
+
+/// Capture and analyze using explicit detection tolerances.
+pub fn capture_and_analyze_with(
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    config: &DetectionConfig,
+) -> Result<DetectionResult, String> {
     let capture_start = Instant::now();
     let img_data = capture_region(x, y, width, height)?;
     let capture_ms = capture_start.elapsed().as_millis() as u64;
 
+    let mut result = analyze_buffer(&img_data, width, height, config);
+    result.capture_ms = capture_ms;
+    Ok(result)
+}
+
+/// Run detection over an already-captured RGBA buffer, applying brightness
+/// auto-gain. `capture_ms` is left at zero for callers to fill in.
+pub fn analyze_buffer(
+    img_data: &[u8],
+    width: u32,
+    height: u32,
+    config: &DetectionConfig,
+) -> DetectionResult {
     let detect_start = Instant::now();
 
+    // Auto-gain: adapt the value thresholds to overall scene brightness so
+    // detection survives dim menus and bright outdoor scenes.
+    let mean_luma = mean_luma(img_data);
+    let gain = brightness_gain(mean_luma);
+    let config = config.with_value_gain(gain);
+
     // Run detections in parallel using rayon
-    let arrow = find_yellow_arrow(&img_data, width, height);
-    let highlight = find_cyan_highlight(&img_data, width, height);
+    let arrow = find_yellow_arrow(img_data, width, height, &config);
+    let highlight = find_cyan_highlight(img_data, width, height, &config);
 
     let detect_ms = detect_start.elapsed().as_millis() as u64;
 
-    Ok(DetectionResult {
+    DetectionResult {
         arrow: arrow.map(|(x, y, c)| Point { x, y, confidence: c }),
         highlight: highlight.map(|(x, y, c)| Point { x, y, confidence: c }),
-        capture_ms,
+        capture_ms: 0,
         detect_ms,
-    })
+        reused: false,
+        mean_luma,
+        gain,
+        objects: None,
+    }
+}
+
+/// Run pixel detection and then populate `objects` from a neural backend,
+/// merging both into a single [`DetectionResult`].
+pub fn analyze_buffer_with_objects(
+    img_data: &[u8],
+    width: u32,
+    height: u32,
+    config: &DetectionConfig,
+    detector: &dyn ObjectDetector,
+) -> DetectionResult {
+    let mut result = analyze_buffer(img_data, width, height, config);
+    result.objects = detector.detect(img_data, width, height).ok();
+    result
+}
+
+/// Reference mean luma (0..255) at which the configured value thresholds are
+/// used unchanged.
+const REFERENCE_LUMA: f32 = 128.0;
+
+/// Compute the mean luma (0..255) of an RGBA buffer.
+fn mean_luma(img_data: &[u8]) -> f32 {
+    let pixels = img_data.len() / 4;
+    if pixels == 0 {
+        return 0.0;
+    }
+    let sum: f64 = img_data
+        .chunks_exact(4)
+        .map(|px| {
+            0.299 * px[0] as f64 + 0.587 * px[1] as f64 + 0.114 * px[2] as f64
+        })
+        .sum();
+    (sum / pixels as f64) as f32
 }
 
+/// Brightness gain for the value thresholds, clamped to a sane range so a
+/// nearly-black or blown-out frame can't drive the floor to a degenerate value.
+fn brightness_gain(mean_luma: f32) -> f32 {
+    (mean_luma / REFERENCE_LUMA).clamp(0.5, 1.5)
+}
+
+/// Side length of the square luma grid used for scene-change detection.
+const SCENE_GRID: usize = 64;
+
+/// Default mean-absolute-difference threshold (0..255) below which two frames
+/// are treated as the same scene and detection is skipped.
+pub const SCENE_CHANGE_THRESHOLD: f32 = 2.0;
+
 pub struct ScreenCapture {
     last_capture: Option<Vec<u8>>,
     width: u32,
     height: u32,
+    /// Cached result from the last frame that actually ran detection.
+    last_result: Option<DetectionResult>,
+    /// Average-pooled luma grid of the previous frame, reused across calls.
+    prev_grid: Vec<f32>,
+    /// Scratch luma grid for the current frame, reused across calls.
+    grid: Vec<f32>,
+    grid_valid: bool,
 }
 
 impl ScreenCapture {
@@ -63,6 +151,10 @@ impl ScreenCapture {
             last_capture: None,
             width: 0,
             height: 0,
+            last_result: None,
+            prev_grid: vec![0.0; SCENE_GRID * SCENE_GRID],
+            grid: vec![0.0; SCENE_GRID * SCENE_GRID],
+            grid_valid: false,
         }
     }
 
@@ -73,4 +165,119 @@ impl ScreenCapture {
         self.last_capture = Some(data);
         Ok(self.last_capture.as_ref().unwrap())
     }
+
+    /// Capture a frame and run detection, short-circuiting to the previous
+    /// result when the scene has not changed beyond `threshold`.
+    ///
+    /// Full detection always runs on the first frame, when the ROI dimensions
+    /// change, and whenever the downscaled scene differs by more than the
+    /// mean-absolute-difference `threshold`.
+    pub fn capture_and_detect_cached(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        config: &DetectionConfig,
+        threshold: f32,
+    ) -> Result<DetectionResult, String> {
+        let capture_start = Instant::now();
+        let img_data = capture_region(x, y, width, height)?;
+        let capture_ms = capture_start.elapsed().as_millis() as u64;
+
+        let dims_changed = width != self.width || height != self.height;
+        downscale_luma(&img_data, width, height, &mut self.grid);
+
+        let can_reuse = self.grid_valid
+            && !dims_changed
+            && self.last_result.is_some()
+            && grid_mad(&self.prev_grid, &self.grid) <= threshold;
+
+        if can_reuse {
+            // Scene is static: reuse the cached detection, but keep the new
+            // grid as the baseline so the next frame is compared against this
+            // one. Gradual drift that stays under `threshold` per frame does
+            // not trigger detection.
+            std::mem::swap(&mut self.prev_grid, &mut self.grid);
+            let mut result = self.last_result.clone().unwrap();
+            result.capture_ms = capture_ms;
+            result.detect_ms = 0;
+            result.reused = true;
+            return Ok(result);
+        }
+
+        let detect_start = Instant::now();
+        let mean_luma = mean_luma(&img_data);
+        let gain = brightness_gain(mean_luma);
+        let config = config.with_value_gain(gain);
+        let arrow = find_yellow_arrow(&img_data, width, height, &config);
+        let highlight = find_cyan_highlight(&img_data, width, height, &config);
+        let detect_ms = detect_start.elapsed().as_millis() as u64;
+
+        let result = DetectionResult {
+            arrow: arrow.map(|(x, y, c)| Point { x, y, confidence: c }),
+            highlight: highlight.map(|(x, y, c)| Point { x, y, confidence: c }),
+            capture_ms,
+            detect_ms,
+            reused: false,
+            mean_luma,
+            gain,
+            objects: None,
+        };
+
+        std::mem::swap(&mut self.prev_grid, &mut self.grid);
+        self.grid_valid = true;
+        self.width = width;
+        self.height = height;
+        self.last_result = Some(result.clone());
+        Ok(result)
+    }
+}
+
+/// Average-pool an RGBA buffer into a `SCENE_GRID`×`SCENE_GRID` luma grid
+/// using `Y = 0.299R + 0.587G + 0.114B`.
+fn downscale_luma(img_data: &[u8], width: u32, height: u32, grid: &mut [f32]) {
+    let width = width as usize;
+    let height = height as usize;
+    for cell in grid.iter_mut() {
+        *cell = 0.0;
+    }
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let mut counts = [0u32; SCENE_GRID * SCENE_GRID];
+    for y in 0..height {
+        let gy = y * SCENE_GRID / height;
+        for x in 0..width {
+            let gx = x * SCENE_GRID / width;
+            let offset = (y * width + x) * 4;
+            if offset + 2 >= img_data.len() {
+                continue;
+            }
+            let luma = 0.299 * img_data[offset] as f32
+                + 0.587 * img_data[offset + 1] as f32
+                + 0.114 * img_data[offset + 2] as f32;
+            let cell = gy * SCENE_GRID + gx;
+            grid[cell] += luma;
+            counts[cell] += 1;
+        }
+    }
+    for (cell, &count) in grid.iter_mut().zip(counts.iter()) {
+        if count > 0 {
+            *cell /= count as f32;
+        }
+    }
+}
+
+/// Mean absolute difference between two luma grids (0..255).
+fn grid_mad(a: &[f32], b: &[f32]) -> f32 {
+    let sum: f32 = a.iter().zip(b.iter()).map(|(p, q)| (p - q).abs()).sum();
+    sum / a.len() as f32
+}
+
+impl Default for ScreenCapture {
+    fn default() -> Self {
+        Self::new()
+    }
 }
@@ -1,49 +1,189 @@
 //! Fast pixel-based detection for Quest Helper arrows and highlights
 
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Per-row scan accumulator: (matched pixel count, sum of x, sum of y).
+type RowStats = (u64, u64, u64);
+
+/// Hue/saturation/value tolerances for matching a single overlay color.
+///
+/// Keying on hue (rather than raw RGB channels) keeps detection stable across
+/// anti-aliased edges and bright-vs-dark backgrounds, where the old fixed
+/// channel cutoffs would drop partially-transparent overlay pixels.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TargetConfig {
+    /// Hue center in degrees (0..360).
+    pub hue_center: f32,
+    /// Half-width of the accepted hue band, in degrees.
+    pub hue_width: f32,
+    /// Minimum saturation (0..1).
+    pub min_saturation: f32,
+    /// Minimum value/brightness (0..1).
+    pub min_value: f32,
+}
+
+/// Per-target detection tolerances threaded through the detection pipeline.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DetectionConfig {
+    pub yellow: TargetConfig,
+    pub cyan: TargetConfig,
+}
+
+impl DetectionConfig {
+    /// Return a copy with both targets' `min_value` floors scaled by `gain`
+    /// (clamped to 0..1), used by the brightness auto-gain step.
+    pub fn with_value_gain(&self, gain: f32) -> Self {
+        let mut cfg = *self;
+        cfg.yellow.min_value = (cfg.yellow.min_value * gain).clamp(0.0, 1.0);
+        cfg.cyan.min_value = (cfg.cyan.min_value * gain).clamp(0.0, 1.0);
+        cfg
+    }
+}
+
+impl Default for DetectionConfig {
+    /// Defaults approximate the legacy RGB cutoffs: yellow
+    /// (`R>200 && G>200 && B<80`, around pure yellow at 60°) is matched at
+    /// 55° ± 10°, cyan (`R<80 && G>180 && B>180`, around pure cyan at 180°)
+    /// at 182° ± 13°.
+    fn default() -> Self {
+        Self {
+            yellow: TargetConfig {
+                hue_center: 55.0,
+                hue_width: 10.0,
+                min_saturation: 0.5,
+                min_value: 0.5,
+            },
+            cyan: TargetConfig {
+                hue_center: 182.0,
+                hue_width: 13.0,
+                min_saturation: 0.5,
+                min_value: 0.5,
+            },
+        }
+    }
+}
+
+/// Test a single RGB pixel against the hue/saturation/value tolerances.
+///
+/// `(g-b)/delta` and friends stay in `[-1, 1]` for any in-gamut pixel, so the
+/// usual `% 6` on the red sextant is a no-op and is dropped. Hue wrap-around is
+/// folded into the circular distance below.
+#[inline]
+fn matches_hsv(
+    r: u8,
+    g: u8,
+    b: u8,
+    hue_center: f32,
+    hue_width: f32,
+    min_saturation: f32,
+    min_value: f32,
+) -> bool {
+    let r = r as f32 * (1.0 / 255.0);
+    let g = g as f32 * (1.0 / 255.0);
+    let b = b as f32 * (1.0 / 255.0);
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    // Guard the divisor without branching; delta == 0 => grey, hue irrelevant.
+    let inv_delta = 1.0 / delta.max(f32::EPSILON);
+
+    // Per-sextant hue candidates; selected by which channel is the max.
+    let h_from_r = (g - b) * inv_delta;
+    let h_from_g = (b - r) * inv_delta + 2.0;
+    let h_from_b = (r - g) * inv_delta + 4.0;
+    let sextant = if max == r {
+        h_from_r
+    } else if max == g {
+        h_from_g
+    } else {
+        h_from_b
+    };
+    let mut hue = 60.0 * sextant;
+    hue += if hue < 0.0 { 360.0 } else { 0.0 };
+
+    // Circular distance to the target hue, without a modulo.
+    let d = (hue - hue_center).abs();
+    let hue_dist = d.min(360.0 - d);
+
+    let saturation = delta / max.max(f32::EPSILON);
+
+    (hue_dist <= hue_width) & (saturation >= min_saturation) & (max >= min_value)
+}
+
+/// Scan one RGBA row for pixels matching the given HSV tolerances, returning
+/// the matched-pixel count and the x/y coordinate sums for the centroid.
+///
+/// Per-pixel matching ([`matches_hsv`]) is scalar; throughput comes from
+/// [`scan_rows`] splitting the rows across the rayon thread pool rather than
+/// from in-row vectorization.
+fn scan_row_hsv(
+    row: &[u8],
+    y: usize,
+    hue_center: f32,
+    hue_width: f32,
+    min_saturation: f32,
+    min_value: f32,
+) -> RowStats {
+    let mut count = 0u64;
+    let mut sum_x = 0u64;
+    let mut sum_y = 0u64;
+    for (x, px) in row.chunks_exact(4).enumerate() {
+        let mask =
+            matches_hsv(px[0], px[1], px[2], hue_center, hue_width, min_saturation, min_value)
+                as u64;
+        count += mask;
+        sum_x += mask * x as u64;
+        sum_y += mask * y as u64;
+    }
+    (count, sum_x, sum_y)
+}
+
+/// Accumulate per-row scan results across all rows, splitting rows over the
+/// rayon thread pool.
+fn scan_rows(img_data: &[u8], width: u32, height: u32, cfg: &TargetConfig) -> RowStats {
+    let row_bytes = width as usize * 4;
+    (0..height as usize)
+        .into_par_iter()
+        .map(|y| {
+            let start = y * row_bytes;
+            scan_row_hsv(
+                &img_data[start..start + row_bytes],
+                y,
+                cfg.hue_center,
+                cfg.hue_width,
+                cfg.min_saturation,
+                cfg.min_value,
+            )
+        })
+        .reduce(|| (0, 0, 0), |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2))
+}
 
 /// Find yellow Quest Helper arrow
 /// Returns (x, y, confidence) if found
-pub fn find_yellow_arrow(img_data: &[u8], width: u32, height: u32) -> Option<(i32, i32, f32)> {
-    // Yellow arrow: R > 200, G > 200, B < 80
+pub fn find_yellow_arrow(
+    img_data: &[u8],
+    width: u32,
+    height: u32,
+    config: &DetectionConfig,
+) -> Option<(i32, i32, f32)> {
+    // Yellow arrow: hue ~45-65°, saturation/value >= 0.5.
     // Image data is RGBA, 4 bytes per pixel
 
-    let pixels_per_row = width as usize;
     let total_pixels = (width * height) as usize;
 
     if img_data.len() < total_pixels * 4 {
         return None;
     }
 
-    // Collect yellow pixel coordinates
-    let yellow_pixels: Vec<(usize, usize)> = (0..total_pixels)
-        .into_par_iter()
-        .filter_map(|i| {
-            let offset = i * 4;
-            let r = img_data[offset];
-            let g = img_data[offset + 1];
-            let b = img_data[offset + 2];
-
-            // Bright yellow detection
-            if r > 200 && g > 200 && b < 80 {
-                let x = i % pixels_per_row;
-                let y = i / pixels_per_row;
-                Some((x, y))
-            } else {
-                None
-            }
-        })
-        .collect();
+    let (count, sum_x, sum_y) = scan_rows(img_data, width, height, &config.yellow);
 
-    if yellow_pixels.len() < 10 {
+    if count < 10 {
         return None;
     }
 
     // Calculate centroid
-    let sum_x: usize = yellow_pixels.iter().map(|(x, _)| x).sum();
-    let sum_y: usize = yellow_pixels.iter().map(|(_, y)| y).sum();
-    let count = yellow_pixels.len();
-
     let center_x = (sum_x / count) as i32;
     let center_y = (sum_y / count) as i32;
 
@@ -55,43 +195,26 @@ pub fn find_yellow_arrow(img_data: &[u8], width: u32, height: u32) -> Option<(i3
 
 /// Find cyan Quest Helper highlight
 /// Returns (x, y, confidence) if found
-pub fn find_cyan_highlight(img_data: &[u8], width: u32, height: u32) -> Option<(i32, i32, f32)> {
-    // Cyan highlight: R < 80, G > 180, B > 180
+pub fn find_cyan_highlight(
+    img_data: &[u8],
+    width: u32,
+    height: u32,
+    config: &DetectionConfig,
+) -> Option<(i32, i32, f32)> {
+    // Cyan highlight: hue ~170-195°, saturation/value >= 0.5.
 
-    let pixels_per_row = width as usize;
     let total_pixels = (width * height) as usize;
 
     if img_data.len() < total_pixels * 4 {
         return None;
     }
 
-    let cyan_pixels: Vec<(usize, usize)> = (0..total_pixels)
-        .into_par_iter()
-        .filter_map(|i| {
-            let offset = i * 4;
-            let r = img_data[offset];
-            let g = img_data[offset + 1];
-            let b = img_data[offset + 2];
-
-            // Cyan/turquoise detection
-            if r < 80 && g > 180 && b > 180 {
-                let x = i % pixels_per_row;
-                let y = i / pixels_per_row;
-                Some((x, y))
-            } else {
-                None
-            }
-        })
-        .collect();
+    let (count, sum_x, sum_y) = scan_rows(img_data, width, height, &config.cyan);
 
-    if cyan_pixels.len() < 20 {
+    if count < 20 {
         return None;
     }
 
-    let sum_x: usize = cyan_pixels.iter().map(|(x, _)| x).sum();
-    let sum_y: usize = cyan_pixels.iter().map(|(_, y)| y).sum();
-    let count = cyan_pixels.len();
-
     let center_x = (sum_x / count) as i32;
     let center_y = (sum_y / count) as i32;
 
@@ -120,7 +243,7 @@ mod tests {
             }
         }
 
-        let result = find_yellow_arrow(&img, 100, 100);
+        let result = find_yellow_arrow(&img, 100, 100, &DetectionConfig::default());
         assert!(result.is_some());
 
         let (x, y, _) = result.unwrap();
@@ -0,0 +1,220 @@
+//! Pluggable detection backends.
+//!
+//! The default backend is the fast pixel-color matcher in [`crate::detection`];
+//! an optional neural backend (gated behind the `onnx` feature) runs a
+//! YOLO-style single-pass model to locate game entities the color heuristics
+//! can't — NPCs, interactables and UI widgets — returning multiple bounding
+//! boxes instead of a single centroid.
+
+use crate::types::Detection;
+
+/// A source of object detections over a captured RGBA ROI.
+pub trait ObjectDetector {
+    fn detect(&self, img: &[u8], width: u32, height: u32) -> Result<Vec<Detection>, String>;
+}
+
+/// Selectable detection backend.
+pub enum Backend {
+    /// No neural detector compiled in / configured.
+    None,
+    #[cfg(feature = "onnx")]
+    Onnx(onnx::OnnxDetector),
+}
+
+impl ObjectDetector for Backend {
+    #[cfg_attr(not(feature = "onnx"), allow(unused_variables))]
+    fn detect(&self, img: &[u8], width: u32, height: u32) -> Result<Vec<Detection>, String> {
+        match self {
+            Backend::None => Err("no object-detection backend compiled in or loaded".to_string()),
+            #[cfg(feature = "onnx")]
+            Backend::Onnx(detector) => detector.detect(img, width, height),
+        }
+    }
+}
+
+#[cfg(feature = "onnx")]
+pub mod onnx {
+    //! ONNX Runtime backend for single-pass object detection.
+
+    use super::ObjectDetector;
+    use crate::types::Detection;
+    use ort::{Environment, Session, SessionBuilder, Value};
+    use std::path::Path;
+    use std::sync::Arc;
+
+    /// Side length of the square model input (e.g. 640 for YOLOv8n).
+    const INPUT_SIZE: usize = 640;
+    /// Minimum confidence for a box to survive before NMS.
+    const CONF_THRESHOLD: f32 = 0.25;
+    /// IoU above which two same-class boxes are considered duplicates.
+    const IOU_THRESHOLD: f32 = 0.45;
+
+    /// A loaded ONNX session plus its class labels, created once and reused.
+    pub struct OnnxDetector {
+        session: Session,
+        classes: Vec<String>,
+    }
+
+    impl OnnxDetector {
+        /// Load a model from `model_path`, labelling outputs with `classes`.
+        pub fn new(model_path: &Path, classes: Vec<String>) -> Result<Self, String> {
+            let environment = Arc::new(
+                Environment::builder()
+                    .with_name("osrs_core")
+                    .build()
+                    .map_err(|e| e.to_string())?,
+            );
+            let session = SessionBuilder::new(&environment)
+                .map_err(|e| e.to_string())?
+                .with_model_from_file(model_path)
+                .map_err(|e| e.to_string())?;
+            Ok(Self { session, classes })
+        }
+
+        /// Letterbox-free resize of the RGBA ROI into a normalized NCHW tensor.
+        fn preprocess(&self, img: &[u8], width: u32, height: u32) -> Vec<f32> {
+            let (width, height) = (width as usize, height as usize);
+            let mut tensor = vec![0.0f32; 3 * INPUT_SIZE * INPUT_SIZE];
+            for oy in 0..INPUT_SIZE {
+                let sy = oy * height / INPUT_SIZE;
+                for ox in 0..INPUT_SIZE {
+                    let sx = ox * width / INPUT_SIZE;
+                    let offset = (sy * width + sx) * 4;
+                    if offset + 2 >= img.len() {
+                        continue;
+                    }
+                    let plane = INPUT_SIZE * INPUT_SIZE;
+                    let pos = oy * INPUT_SIZE + ox;
+                    tensor[pos] = img[offset] as f32 / 255.0;
+                    tensor[plane + pos] = img[offset + 1] as f32 / 255.0;
+                    tensor[2 * plane + pos] = img[offset + 2] as f32 / 255.0;
+                }
+            }
+            tensor
+        }
+    }
+
+    impl ObjectDetector for OnnxDetector {
+        fn detect(&self, img: &[u8], width: u32, height: u32) -> Result<Vec<Detection>, String> {
+            let input = self.preprocess(img, width, height);
+            let shape = [1usize, 3, INPUT_SIZE, INPUT_SIZE];
+            let tensor =
+                Value::from_array((shape, input)).map_err(|e| e.to_string())?;
+            let outputs = self
+                .session
+                .run(vec![tensor])
+                .map_err(|e| e.to_string())?;
+
+            let (out_shape, raw) = outputs[0]
+                .try_extract_raw_tensor::<f32>()
+                .map_err(|e| e.to_string())?;
+
+            let boxes = self.decode(&out_shape, raw, width, height);
+            Ok(non_max_suppression(boxes, IOU_THRESHOLD))
+        }
+    }
+
+    impl OnnxDetector {
+        /// Decode a `[1, 4 + num_classes, num_anchors]` YOLO output tensor into
+        /// confidence-thresholded boxes in ROI pixel space.
+        fn decode(
+            &self,
+            shape: &[i64],
+            raw: &[f32],
+            width: u32,
+            height: u32,
+        ) -> Vec<Detection> {
+            let channels = shape[1] as usize;
+            let anchors = shape[2] as usize;
+            let num_classes = channels.saturating_sub(4);
+            let sx = width as f32 / INPUT_SIZE as f32;
+            let sy = height as f32 / INPUT_SIZE as f32;
+
+            let mut boxes = Vec::new();
+            for a in 0..anchors {
+                // Channel-major layout: value(c, a) = raw[c * anchors + a].
+                let cx = raw[a];
+                let cy = raw[anchors + a];
+                let w = raw[2 * anchors + a];
+                let h = raw[3 * anchors + a];
+
+                let mut best_class = 0;
+                let mut best_score = 0.0f32;
+                for c in 0..num_classes {
+                    let score = raw[(4 + c) * anchors + a];
+                    if score > best_score {
+                        best_score = score;
+                        best_class = c;
+                    }
+                }
+                if best_score < CONF_THRESHOLD {
+                    continue;
+                }
+
+                let bx = ((cx - w / 2.0) * sx) as i32;
+                let by = ((cy - h / 2.0) * sy) as i32;
+                boxes.push(Detection {
+                    class: self
+                        .classes
+                        .get(best_class)
+                        .cloned()
+                        .unwrap_or_else(|| best_class.to_string()),
+                    bbox: (bx, by, (w * sx) as i32, (h * sy) as i32),
+                    confidence: best_score,
+                });
+            }
+            boxes
+        }
+    }
+
+    /// Intersection-over-union of two `(x, y, w, h)` boxes.
+    fn iou(a: (i32, i32, i32, i32), b: (i32, i32, i32, i32)) -> f32 {
+        let ax2 = a.0 + a.2;
+        let ay2 = a.1 + a.3;
+        let bx2 = b.0 + b.2;
+        let by2 = b.1 + b.3;
+
+        let ix = (ax2.min(bx2) - a.0.max(b.0)).max(0);
+        let iy = (ay2.min(by2) - a.1.max(b.1)).max(0);
+        let inter = (ix * iy) as f32;
+        let union = (a.2 * a.3 + b.2 * b.3) as f32 - inter;
+        if union <= 0.0 {
+            0.0
+        } else {
+            inter / union
+        }
+    }
+
+    /// Greedy per-class non-max suppression: keep the highest-confidence box,
+    /// drop same-class boxes overlapping it beyond `iou_threshold`.
+    fn non_max_suppression(mut boxes: Vec<Detection>, iou_threshold: f32) -> Vec<Detection> {
+        boxes.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        let mut kept: Vec<Detection> = Vec::new();
+        for candidate in boxes {
+            let suppressed = kept.iter().any(|k| {
+                k.class == candidate.class && iou(k.bbox, candidate.bbox) > iou_threshold
+            });
+            if !suppressed {
+                kept.push(candidate);
+            }
+        }
+        kept
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_nms_dedupes_overlap() {
+            let boxes = vec![
+                Detection { class: "npc".into(), bbox: (10, 10, 20, 20), confidence: 0.9 },
+                Detection { class: "npc".into(), bbox: (12, 12, 20, 20), confidence: 0.8 },
+                Detection { class: "npc".into(), bbox: (100, 100, 20, 20), confidence: 0.7 },
+            ];
+            let kept = non_max_suppression(boxes, 0.45);
+            assert_eq!(kept.len(), 2);
+            assert_eq!(kept[0].confidence, 0.9);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+//! Cross-validation of RuneLite snapshots against the official OSRS
+//! hiscores API.
+//!
+//! RuneLite data is otherwise trusted blindly; this module pulls canonical
+//! skill levels/xp for an account and reconciles them against
+//! [`AccountInfo::skills`], flagging divergences and marking the snapshot
+//! stale when the authoritative source disagrees.
+//!
+//! World-list reconciliation is intentionally *not* implemented: the request
+//! also called for reconciling the game world list, but `SnapshotSchema`
+//! carries no current-world identifier — `RuneliteData::player_world` holds
+//! map coordinates `(x, y, plane)`, not a world number — so there is no field
+//! to reconcile a world list against. If a world-id field is added to the
+//! schema, wire a `fetch_worlds` path through [`HiscoresSource`] here.
+
+use crate::integration::{SkillInfo, SnapshotSchema, ValidationResult};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Skill order used by the official hiscores CSV, after its leading Overall
+/// line.
+const SKILL_NAMES: &[&str] = &[
+    "Attack",
+    "Defence",
+    "Strength",
+    "Hitpoints",
+    "Ranged",
+    "Prayer",
+    "Magic",
+    "Cooking",
+    "Woodcutting",
+    "Fletching",
+    "Fishing",
+    "Firemaking",
+    "Crafting",
+    "Smithing",
+    "Mining",
+    "Herblore",
+    "Agility",
+    "Thieving",
+    "Slayer",
+    "Farming",
+    "Runecraft",
+    "Hunter",
+    "Construction",
+];
+
+/// Authoritative source of canonical account data, behind a trait so tests
+/// can mock it without hitting the network.
+pub trait HiscoresSource {
+    /// Fetch canonical per-skill level/xp for `name`.
+    fn fetch_skills(&self, name: &str) -> Result<HashMap<String, SkillInfo>, String>;
+}
+
+/// Official OSRS hiscores endpoint.
+pub struct OsrsHiscores {
+    hiscores_url: String,
+}
+
+impl Default for OsrsHiscores {
+    fn default() -> Self {
+        Self {
+            hiscores_url:
+                "https://secure.runescape.com/m=hiscore_oldschool/index_lite.ws?player="
+                    .to_string(),
+        }
+    }
+}
+
+impl HiscoresSource for OsrsHiscores {
+    fn fetch_skills(&self, name: &str) -> Result<HashMap<String, SkillInfo>, String> {
+        let url = format!("{}{}", self.hiscores_url, urlencode(name));
+        let body = ureq::get(&url)
+            .call()
+            .map_err(|e| e.to_string())?
+            .into_string()
+            .map_err(|e| e.to_string())?;
+        parse_hiscores_csv(&body)
+    }
+}
+
+/// Minimal percent-encoding for the player-name query parameter.
+fn urlencode(name: &str) -> String {
+    name.chars()
+        .map(|c| if c == ' ' { "%20".to_string() } else { c.to_string() })
+        .collect()
+}
+
+/// Parse the hiscores CSV (`rank,level,xp` per line) into a skill map.
+fn parse_hiscores_csv(body: &str) -> Result<HashMap<String, SkillInfo>, String> {
+    let mut skills = HashMap::new();
+    // Line 0 is the Overall aggregate; skills follow in `SKILL_NAMES` order.
+    for (line, &skill) in body.lines().skip(1).zip(SKILL_NAMES.iter()) {
+        let mut parts = line.split(',');
+        let _rank = parts.next();
+        let level = parts
+            .next()
+            .and_then(|s| s.parse::<i64>().ok())
+            .unwrap_or(-1);
+        let xp = parts
+            .next()
+            .and_then(|s| s.parse::<i64>().ok())
+            .unwrap_or(-1);
+        if level < 0 {
+            continue;
+        }
+        skills.insert(
+            skill.to_string(),
+            SkillInfo {
+                level: level as u32,
+                xp: xp.max(0) as u64,
+            },
+        );
+    }
+    if skills.is_empty() {
+        return Err("hiscores returned no parseable skills".to_string());
+    }
+    Ok(skills)
+}
+
+/// TTL cache keyed by account name, wrapping any [`HiscoresSource`] so we
+/// don't hammer the API every tick.
+pub struct CachedHiscores<S: HiscoresSource> {
+    inner: S,
+    ttl: Duration,
+    cache: Mutex<HashMap<String, (Instant, HashMap<String, SkillInfo>)>>,
+}
+
+impl<S: HiscoresSource> CachedHiscores<S> {
+    pub fn new(inner: S, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<S: HiscoresSource> HiscoresSource for CachedHiscores<S> {
+    fn fetch_skills(&self, name: &str) -> Result<HashMap<String, SkillInfo>, String> {
+        if let Ok(cache) = self.cache.lock() {
+            if let Some((fetched_at, skills)) = cache.get(name) {
+                if fetched_at.elapsed() < self.ttl {
+                    return Ok(skills.clone());
+                }
+            }
+        }
+        let skills = self.inner.fetch_skills(name)?;
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(name.to_string(), (Instant::now(), skills.clone()));
+        }
+        Ok(skills)
+    }
+}
+
+/// Reconcile a snapshot's account skills against the authoritative source.
+///
+/// Missing skills are filled in from the hiscores, divergences are recorded as
+/// errors, and the snapshot is marked stale whenever the authoritative source
+/// contradicts a level RuneLite reported.
+pub fn reconcile_account<S: HiscoresSource>(
+    source: &S,
+    snapshot: &mut SnapshotSchema,
+) -> ValidationResult {
+    let start = Instant::now();
+    let mut errors = Vec::new();
+
+    let name = snapshot.account.name.clone();
+    if name.is_empty() {
+        errors.push("Cannot reconcile account: missing name".to_string());
+        return ValidationResult {
+            valid: false,
+            errors,
+            validation_ms: start.elapsed().as_millis() as u64,
+        };
+    }
+
+    let canonical = match source.fetch_skills(&name) {
+        Ok(canonical) => canonical,
+        Err(e) => {
+            errors.push(format!("Hiscores fetch failed for '{name}': {e}"));
+            return ValidationResult {
+                valid: false,
+                errors,
+                validation_ms: start.elapsed().as_millis() as u64,
+            };
+        }
+    };
+
+    for (skill, authoritative) in &canonical {
+        match snapshot.account.skills.get(skill) {
+            None => {
+                snapshot.account.skills.insert(skill.clone(), authoritative.clone());
+            }
+            Some(reported) if reported.level != authoritative.level => {
+                errors.push(format!(
+                    "Skill '{skill}': RuneLite reports level {} but hiscores report {}",
+                    reported.level, authoritative.level
+                ));
+                snapshot.stale = true;
+            }
+            Some(_) => {}
+        }
+    }
+
+    ValidationResult {
+        valid: errors.is_empty(),
+        errors,
+        validation_ms: start.elapsed().as_millis() as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockSource {
+        skills: HashMap<String, SkillInfo>,
+    }
+
+    impl HiscoresSource for MockSource {
+        fn fetch_skills(&self, _name: &str) -> Result<HashMap<String, SkillInfo>, String> {
+            Ok(self.skills.clone())
+        }
+    }
+
+    fn source_with(level: u32) -> MockSource {
+        let mut skills = HashMap::new();
+        skills.insert("Attack".to_string(), SkillInfo { level, xp: 100_000 });
+        MockSource { skills }
+    }
+
+    #[test]
+    fn test_reconcile_fills_missing_skill() {
+        let source = source_with(60);
+        let mut snapshot = SnapshotSchema {
+            account: crate::integration::AccountInfo {
+                name: "Zezima".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let result = reconcile_account(&source, &mut snapshot);
+        assert!(result.valid);
+        assert_eq!(snapshot.account.skills.get("Attack").unwrap().level, 60);
+    }
+
+    #[test]
+    fn test_reconcile_flags_divergence() {
+        let source = source_with(60);
+        let mut snapshot = SnapshotSchema {
+            account: crate::integration::AccountInfo {
+                name: "Zezima".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        snapshot
+            .account
+            .skills
+            .insert("Attack".to_string(), SkillInfo { level: 99, xp: 0 });
+
+        let result = reconcile_account(&source, &mut snapshot);
+        assert!(!result.valid);
+        assert!(snapshot.stale);
+        assert!(result.errors.iter().any(|e| e.contains("Attack")));
+    }
+
+    struct CountingSource {
+        inner: MockSource,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl HiscoresSource for CountingSource {
+        fn fetch_skills(&self, name: &str) -> Result<HashMap<String, SkillInfo>, String> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.inner.fetch_skills(name)
+        }
+    }
+
+    #[test]
+    fn test_cache_serves_within_ttl() {
+        let counting = CountingSource {
+            inner: source_with(70),
+            calls: std::sync::atomic::AtomicUsize::new(0),
+        };
+        let cache = CachedHiscores::new(counting, Duration::from_secs(60));
+        let first = cache.fetch_skills("Zezima").unwrap();
+        let second = cache.fetch_skills("Zezima").unwrap();
+        assert_eq!(first.get("Attack").unwrap().level, 70);
+        assert_eq!(second.get("Attack").unwrap().level, 70);
+        assert_eq!(cache.inner.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+}
@@ -8,6 +8,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
 use std::time::Instant;
 
 /// Timing budgets in milliseconds
@@ -301,42 +302,15 @@ pub fn validate_snapshot(snapshot: &SnapshotSchema) -> ValidationResult {
 // REGION INFERENCE (FAST)
 // =============================================================================
 
-/// Fast region inference from world coordinates
+/// Fast region inference from world coordinates, backed by the data-driven
+/// [`crate::regions`] map (plane-agnostic).
 pub fn infer_region(x: i32, y: i32) -> &'static str {
-    // Tutorial Island
-    if x >= 3050 && x <= 3150 && y >= 3050 && y <= 3150 {
-        return "Tutorial Island";
-    }
-    // Lumbridge
-    if x >= 3200 && x <= 3250 && y >= 3200 && y <= 3250 {
-        return "Lumbridge";
-    }
-    // Varrock
-    if x >= 3180 && x <= 3290 && y >= 3380 && y <= 3500 {
-        return "Varrock";
-    }
-    // Falador
-    if x >= 2940 && x <= 3040 && y >= 3310 && y <= 3400 {
-        return "Falador";
-    }
-    // Draynor
-    if x >= 3080 && x <= 3120 && y >= 3230 && y <= 3280 {
-        return "Draynor";
-    }
-    // Al Kharid
-    if x >= 3270 && x <= 3330 && y >= 3140 && y <= 3200 {
-        return "Al Kharid";
-    }
-    // Edgeville
-    if x >= 3080 && x <= 3110 && y >= 3480 && y <= 3520 {
-        return "Edgeville";
-    }
-    // Barbarian Village
-    if x >= 3070 && x <= 3110 && y >= 3410 && y <= 3440 {
-        return "Barbarian Village";
-    }
+    crate::regions::infer_region(x, y)
+}
 
-    "unknown"
+/// Region inference constrained to a specific `plane`.
+pub fn infer_region_on_plane(x: i32, y: i32, plane: i32) -> &'static str {
+    crate::regions::infer_region_on_plane(x, y, plane)
 }
 
 /// Infer tutorial phase from varbit value
@@ -390,6 +364,163 @@ pub fn check_stage_timing(stage: &str, latency_ms: u64) -> PipelineMetrics {
     }
 }
 
+/// Number of recent runs retained per stage for percentile estimation.
+const STAGE_WINDOW: usize = 256;
+
+/// Rolling window of recent samples for a single stage.
+#[derive(Default)]
+struct StageWindow {
+    latencies: Vec<u64>,
+    over_budget: Vec<bool>,
+    head: usize,
+}
+
+impl StageWindow {
+    fn observe(&mut self, latency_ms: u64, over_budget: bool) {
+        if self.latencies.len() < STAGE_WINDOW {
+            self.latencies.push(latency_ms);
+            self.over_budget.push(over_budget);
+        } else {
+            self.latencies[self.head] = latency_ms;
+            self.over_budget[self.head] = over_budget;
+            self.head = (self.head + 1) % STAGE_WINDOW;
+        }
+    }
+
+    fn stats(&self) -> StageStats {
+        let count = self.latencies.len() as u64;
+        if count == 0 {
+            return StageStats::default();
+        }
+        let mut sorted = self.latencies.clone();
+        sorted.sort_unstable();
+        let over = self.over_budget.iter().filter(|&&o| o).count();
+        StageStats {
+            count,
+            p50_ms: percentile(&sorted, 0.50),
+            p95_ms: percentile(&sorted, 0.95),
+            p99_ms: percentile(&sorted, 0.99),
+            over_budget_rate: over as f32 / count as f32,
+        }
+    }
+}
+
+/// Nearest-rank percentile over a pre-sorted slice.
+fn percentile(sorted: &[u64], q: f32) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (q * sorted.len() as f32).ceil() as usize;
+    let idx = rank.saturating_sub(1).min(sorted.len() - 1);
+    sorted[idx]
+}
+
+fn stage_windows() -> &'static Mutex<HashMap<String, StageWindow>> {
+    static WINDOWS: OnceLock<Mutex<HashMap<String, StageWindow>>> = OnceLock::new();
+    WINDOWS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Feed one stage observation into the rolling percentile windows. Called from
+/// the single shared recording path in [`crate::metrics::record_stage_timing`]
+/// so window stats and the Prometheus registry never diverge.
+pub(crate) fn observe_stage_window(stage_name: &str, latency_ms: u64, over_budget: bool) {
+    if let Ok(mut windows) = stage_windows().lock() {
+        windows
+            .entry(stage_name.to_string())
+            .or_default()
+            .observe(latency_ms, over_budget);
+    }
+}
+
+/// Per-stage rolling latency statistics.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StageStats {
+    pub count: u64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub p99_ms: u64,
+    pub over_budget_rate: f32,
+}
+
+/// Snapshot of rolling statistics across all observed stages.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PipelineStats {
+    pub stages: HashMap<String, StageStats>,
+}
+
+/// Per-stage result returned while accumulating a [`PipelineRun`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageStep {
+    pub metrics: PipelineMetrics,
+    /// Budget remaining for the rest of the tick after this stage.
+    pub remaining_ms: u64,
+    /// True once cumulative latency has exceeded `TOTAL_BUDGET_MS`.
+    pub blown: bool,
+}
+
+/// Accumulator for a single pipeline tick across all stages.
+///
+/// A caller feeds in each stage's measured latency; after every stage it
+/// learns how much of `TOTAL_BUDGET_MS` remains, so the decision stage can be
+/// told to degrade gracefully. Once the cumulative latency blows the budget
+/// the run is flagged so downstream execution can skip sending a stale intent.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineRun {
+    elapsed_ms: u64,
+    blown: bool,
+}
+
+impl PipelineRun {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a completed stage, updating the rolling window and the run's
+    /// cumulative budget. Goes through the shared metrics recording path so the
+    /// Prometheus registry and the percentile windows stay in sync.
+    pub fn record(&mut self, stage: &str, latency_ms: u64) -> StageStep {
+        let metrics = crate::metrics::record_stage_timing(stage, latency_ms);
+
+        self.elapsed_ms += latency_ms;
+        if self.elapsed_ms > TOTAL_BUDGET_MS {
+            self.blown = true;
+        }
+
+        StageStep {
+            metrics,
+            remaining_ms: TOTAL_BUDGET_MS.saturating_sub(self.elapsed_ms),
+            blown: self.blown,
+        }
+    }
+
+    /// Budget remaining for the rest of the tick.
+    pub fn remaining_ms(&self) -> u64 {
+        TOTAL_BUDGET_MS.saturating_sub(self.elapsed_ms)
+    }
+
+    /// Total latency accumulated so far.
+    pub fn total_ms(&self) -> u64 {
+        self.elapsed_ms
+    }
+
+    /// Whether the tick has exceeded `TOTAL_BUDGET_MS`.
+    pub fn is_blown(&self) -> bool {
+        self.blown
+    }
+}
+
+/// Snapshot the rolling per-stage statistics.
+pub fn pipeline_stats() -> PipelineStats {
+    let stages = match stage_windows().lock() {
+        Ok(windows) => windows
+            .iter()
+            .map(|(name, window)| (name.clone(), window.stats()))
+            .collect(),
+        Err(_) => HashMap::new(),
+    };
+    PipelineStats { stages }
+}
+
 // =============================================================================
 // NORMALIZATION
 // =============================================================================
@@ -415,7 +546,7 @@ pub fn merge_snapshot_data(
     // Set location from player world coords
     if let Some((x, y, plane)) = runelite.player_world {
         snapshot.derived.location.coordinates = WorldCoord { x, y, plane };
-        snapshot.derived.location.region = infer_region(x, y).to_string();
+        snapshot.derived.location.region = infer_region_on_plane(x, y, plane).to_string();
     }
 
     // Add detection results to cues
@@ -485,6 +616,36 @@ mod tests {
         assert_eq!(infer_region(0, 0), "unknown");
     }
 
+    #[test]
+    fn test_pipeline_run_reports_remaining() {
+        let mut run = PipelineRun::new();
+        let step = run.record("rsprox_poll", 40);
+        assert_eq!(step.remaining_ms, TOTAL_BUDGET_MS - 40);
+        assert!(!step.blown);
+
+        let step = run.record("perception", 90);
+        assert_eq!(step.remaining_ms, TOTAL_BUDGET_MS - 130);
+        assert!(!step.blown);
+    }
+
+    #[test]
+    fn test_pipeline_run_blows_budget() {
+        let mut run = PipelineRun::new();
+        run.record("rsprox_poll", 300);
+        let step = run.record("perception", 400);
+        assert!(step.blown);
+        assert!(run.is_blown());
+        assert_eq!(run.remaining_ms(), 0);
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let sorted: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&sorted, 0.50), 50);
+        assert_eq!(percentile(&sorted, 0.95), 95);
+        assert_eq!(percentile(&sorted, 0.99), 99);
+    }
+
     #[test]
     fn test_infer_tutorial_phase() {
         assert_eq!(infer_tutorial_phase(0), "character_creation");
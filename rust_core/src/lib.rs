@@ -9,14 +9,20 @@
 //! - Pipeline timing enforcement
 
 use pyo3::prelude::*;
+use std::sync::{Mutex, OnceLock};
 
 mod capture;
 mod detection;
+mod detector;
+mod hiscores;
 mod integration;
+mod metrics;
+mod regions;
+mod replay;
 mod types;
 
 use capture::ScreenCapture;
-use detection::{find_yellow_arrow, find_cyan_highlight};
+use detection::{find_yellow_arrow, find_cyan_highlight, DetectionConfig, TargetConfig};
 use integration::{validate_intent, validate_snapshot, infer_region, infer_tutorial_phase, check_stage_timing};
 use types::DetectionResult;
 
@@ -30,13 +36,35 @@ fn capture_region(x: i32, y: i32, width: u32, height: u32) -> PyResult<Vec<u8>>
 /// Find yellow Quest Helper arrow in image data
 #[pyfunction]
 fn detect_arrow(img_data: Vec<u8>, width: u32, height: u32) -> PyResult<Option<(i32, i32, f32)>> {
-    Ok(find_yellow_arrow(&img_data, width, height))
+    Ok(find_yellow_arrow(&img_data, width, height, &DetectionConfig::default()))
 }
 
 /// Find cyan highlight in image data
 #[pyfunction]
 fn detect_highlight(img_data: Vec<u8>, width: u32, height: u32) -> PyResult<Option<(i32, i32, f32)>> {
-    Ok(find_cyan_highlight(&img_data, width, height))
+    Ok(find_cyan_highlight(&img_data, width, height, &DetectionConfig::default()))
+}
+
+/// Find yellow arrow via explicit HSV tolerances (hue center/width, min sat/value)
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+fn detect_arrow_hsv(
+    img_data: Vec<u8>,
+    width: u32,
+    height: u32,
+    hue_center: f32,
+    hue_width: f32,
+    min_saturation: f32,
+    min_value: f32,
+) -> PyResult<Option<(i32, i32, f32)>> {
+    let mut config = DetectionConfig::default();
+    config.yellow = TargetConfig {
+        hue_center,
+        hue_width,
+        min_saturation,
+        min_value,
+    };
+    Ok(find_yellow_arrow(&img_data, width, height, &config))
 }
 
 /// Capture and detect in one call (fastest)
@@ -48,6 +76,85 @@ fn capture_and_detect(x: i32, y: i32, width: u32, height: u32) -> PyResult<Strin
     Ok(serde_json::to_string(&result).unwrap_or_default())
 }
 
+/// Shared stateful capturer backing scene-change gating across calls.
+static SCENE_CAPTURE: OnceLock<Mutex<ScreenCapture>> = OnceLock::new();
+
+/// Capture and detect, skipping detection when the scene is static.
+#[pyfunction]
+fn capture_and_detect_cached(x: i32, y: i32, width: u32, height: u32, threshold: f32) -> PyResult<String> {
+    let capture = SCENE_CAPTURE.get_or_init(|| Mutex::new(ScreenCapture::new()));
+    let mut capture = capture
+        .lock()
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+    let result = capture
+        .capture_and_detect_cached(x, y, width, height, &DetectionConfig::default(), threshold)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+    Ok(serde_json::to_string(&result).unwrap_or_default())
+}
+
+/// Capture a region, save it to a PNG for later replay, and return the
+/// detection result as JSON. Used by the Python side to log production frames.
+#[pyfunction]
+fn capture_and_save(x: i32, y: i32, width: u32, height: u32, path: &str) -> PyResult<String> {
+    let img_data = capture::capture_region(x, y, width, height)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+    replay::save_capture_png(&img_data, width, height, std::path::Path::new(path))
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+    let result = capture::analyze_buffer(&img_data, width, height, &DetectionConfig::default());
+    Ok(serde_json::to_string(&result).unwrap_or_default())
+}
+
+/// The process-wide object-detection backend, created once and reused.
+fn object_backend() -> &'static detector::Backend {
+    static DETECTOR: OnceLock<detector::Backend> = OnceLock::new();
+    DETECTOR.get_or_init(|| {
+        #[cfg(feature = "onnx")]
+        {
+            let model = std::env::var("OSRS_ONNX_MODEL").unwrap_or_default();
+            match detector::onnx::OnnxDetector::new(std::path::Path::new(&model), Vec::new()) {
+                Ok(d) => detector::Backend::Onnx(d),
+                Err(_) => detector::Backend::None,
+            }
+        }
+        #[cfg(not(feature = "onnx"))]
+        {
+            detector::Backend::None
+        }
+    })
+}
+
+/// Run the neural object detector over an RGBA ROI, returning a JSON array of
+/// `{class, bbox, confidence}` boxes. Requires the `onnx` feature; without it
+/// this returns an error describing the missing backend.
+#[pyfunction]
+fn detect_objects(img: Vec<u8>, width: u32, height: u32) -> PyResult<String> {
+    use detector::ObjectDetector;
+
+    let detections = object_backend()
+        .detect(&img, width, height)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e))?;
+    Ok(serde_json::to_string(&detections).unwrap_or_default())
+}
+
+/// Run pixel detection and the neural object detector together, returning a
+/// full `DetectionResult` JSON with `objects` populated when a backend is
+/// available.
+#[pyfunction]
+fn detect_with_objects(img: Vec<u8>, width: u32, height: u32) -> PyResult<String> {
+    let result = capture::analyze_buffer_with_objects(
+        &img,
+        width,
+        height,
+        &DetectionConfig::default(),
+        object_backend(),
+    );
+    Ok(serde_json::to_string(&result).unwrap_or_default())
+}
+
 // =============================================================================
 // INTEGRATION LAYER FUNCTIONS
 // =============================================================================
@@ -59,6 +166,7 @@ fn validate_action_intent(intent_json: &str) -> PyResult<String> {
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
 
     let result = integration::validate_intent(&intent);
+    metrics::record_validation_result("validate_intent", &result);
     Ok(serde_json::to_string(&result).unwrap_or_default())
 }
 
@@ -69,15 +177,44 @@ fn validate_snapshot_schema(snapshot_json: &str) -> PyResult<String> {
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
 
     let result = integration::validate_snapshot(&snapshot);
+    metrics::record_validation_result("validate_snapshot", &result);
     Ok(serde_json::to_string(&result).unwrap_or_default())
 }
 
+/// Cross-validate a snapshot's account skills against the official hiscores.
+/// Returns the reconciled snapshot JSON merged with the validation result.
+#[pyfunction]
+fn reconcile_snapshot(snapshot_json: &str) -> PyResult<String> {
+    use hiscores::{CachedHiscores, OsrsHiscores};
+    use std::time::Duration;
+
+    static HISCORES: OnceLock<CachedHiscores<OsrsHiscores>> = OnceLock::new();
+    let source = HISCORES
+        .get_or_init(|| CachedHiscores::new(OsrsHiscores::default(), Duration::from_secs(300)));
+
+    let mut snapshot: integration::SnapshotSchema = serde_json::from_str(snapshot_json)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+
+    let result = hiscores::reconcile_account(source, &mut snapshot);
+    let combined = serde_json::json!({
+        "snapshot": snapshot,
+        "reconciliation": result,
+    });
+    Ok(serde_json::to_string(&combined).unwrap_or_default())
+}
+
 /// Fast region inference from world coordinates
 #[pyfunction]
 fn get_region(x: i32, y: i32) -> String {
     integration::infer_region(x, y).to_string()
 }
 
+/// Fast plane-aware region inference from world coordinates
+#[pyfunction]
+fn get_region_on_plane(x: i32, y: i32, plane: i32) -> String {
+    integration::infer_region_on_plane(x, y, plane).to_string()
+}
+
 /// Fast tutorial phase inference
 #[pyfunction]
 fn get_tutorial_phase(varbit_281: i32) -> String {
@@ -87,10 +224,57 @@ fn get_tutorial_phase(varbit_281: i32) -> String {
 /// Check pipeline stage timing
 #[pyfunction]
 fn check_timing(stage: &str, latency_ms: u64) -> PyResult<String> {
-    let result = integration::check_stage_timing(stage, latency_ms);
+    let result = metrics::record_stage_timing(stage, latency_ms);
     Ok(serde_json::to_string(&result).unwrap_or_default())
 }
 
+/// Render the pipeline metrics registry in Prometheus text format
+#[pyfunction]
+fn render_metrics() -> PyResult<String> {
+    Ok(metrics::render_prometheus())
+}
+
+/// Record the most recent total-pipeline latency gauge
+#[pyfunction]
+fn record_total_latency(latency_ms: u64) {
+    metrics::record_total_latency(latency_ms);
+}
+
+/// Spawn a background HTTP `/metrics` exporter bound to `addr`
+#[pyfunction]
+fn start_metrics_server(addr: String) -> PyResult<()> {
+    std::thread::spawn(move || {
+        let _ = metrics::serve(&addr);
+    });
+    Ok(())
+}
+
+/// Accumulate a full tick's stage timings and report deadline enforcement.
+/// `stages` is a list of `(name, latency_ms)` fed in pipeline order; the
+/// result carries the per-stage remaining budget and a `blown` flag.
+#[pyfunction]
+fn run_pipeline_budget(stages: Vec<(String, u64)>) -> PyResult<String> {
+    let mut run = integration::PipelineRun::new();
+    let steps: Vec<integration::StageStep> = stages
+        .iter()
+        .map(|(stage, latency)| run.record(stage, *latency))
+        .collect();
+
+    let summary = serde_json::json!({
+        "steps": steps,
+        "total_ms": run.total_ms(),
+        "remaining_ms": run.remaining_ms(),
+        "blown": run.is_blown(),
+    });
+    Ok(serde_json::to_string(&summary).unwrap_or_default())
+}
+
+/// Snapshot rolling per-stage latency percentiles and over-budget rates.
+#[pyfunction]
+fn get_pipeline_stats() -> PyResult<String> {
+    Ok(serde_json::to_string(&integration::pipeline_stats()).unwrap_or_default())
+}
+
 /// Get timing budgets
 #[pyfunction]
 fn get_timing_budgets() -> PyResult<String> {
@@ -112,15 +296,27 @@ fn osrs_core(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(capture_region, m)?)?;
     m.add_function(wrap_pyfunction!(detect_arrow, m)?)?;
     m.add_function(wrap_pyfunction!(detect_highlight, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_arrow_hsv, m)?)?;
     m.add_function(wrap_pyfunction!(capture_and_detect, m)?)?;
+    m.add_function(wrap_pyfunction!(capture_and_detect_cached, m)?)?;
+    m.add_function(wrap_pyfunction!(capture_and_save, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_objects, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_with_objects, m)?)?;
 
     // Integration layer functions
     m.add_function(wrap_pyfunction!(validate_action_intent, m)?)?;
     m.add_function(wrap_pyfunction!(validate_snapshot_schema, m)?)?;
+    m.add_function(wrap_pyfunction!(reconcile_snapshot, m)?)?;
     m.add_function(wrap_pyfunction!(get_region, m)?)?;
+    m.add_function(wrap_pyfunction!(get_region_on_plane, m)?)?;
     m.add_function(wrap_pyfunction!(get_tutorial_phase, m)?)?;
     m.add_function(wrap_pyfunction!(check_timing, m)?)?;
     m.add_function(wrap_pyfunction!(get_timing_budgets, m)?)?;
+    m.add_function(wrap_pyfunction!(run_pipeline_budget, m)?)?;
+    m.add_function(wrap_pyfunction!(get_pipeline_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(render_metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(record_total_latency, m)?)?;
+    m.add_function(wrap_pyfunction!(start_metrics_server, m)?)?;
 
     Ok(())
 }
@@ -0,0 +1,231 @@
+//! Prometheus metrics for the pipeline timing subsystem.
+//!
+//! A thread-safe registry accumulates per-stage latency histograms,
+//! over-budget counters and validation failure counts. [`render_prometheus`]
+//! serializes the registry into the Prometheus text exposition format, and
+//! [`serve`] exposes it over a minimal HTTP `/metrics` endpoint so a real
+//! scraper can alert when a stage blows its budget.
+
+use crate::integration::{check_stage_timing, PipelineMetrics, ValidationResult, TOTAL_BUDGET_MS};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Upper bounds (ms) of the fixed latency buckets; `+Inf` is implicit.
+const BUCKETS: [u64; 7] = [10, 25, 50, 100, 200, 400, 600];
+
+/// Per-stage latency histogram with over-budget counter.
+#[derive(Debug, Default, Clone)]
+struct StageHist {
+    /// Cumulative bucket counts aligned with [`BUCKETS`] plus a trailing
+    /// `+Inf` bucket.
+    buckets: [u64; 8],
+    sum_ms: u64,
+    count: u64,
+    over_budget: u64,
+}
+
+impl StageHist {
+    fn observe(&mut self, latency_ms: u64, over_budget: bool) {
+        for (i, &bound) in BUCKETS.iter().enumerate() {
+            if latency_ms <= bound {
+                self.buckets[i] += 1;
+            }
+        }
+        self.buckets[BUCKETS.len()] += 1; // +Inf
+        self.sum_ms += latency_ms;
+        self.count += 1;
+        if over_budget {
+            self.over_budget += 1;
+        }
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    stages: HashMap<String, StageHist>,
+    /// Validation failures keyed by `"<source>:<kind>"`.
+    validation_failures: HashMap<String, u64>,
+    /// Most recent total-pipeline latency (ms).
+    last_total_latency_ms: u64,
+}
+
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+/// Single shared recording path for a stage timing: checks the stage against
+/// its budget, updates the Prometheus histogram, and feeds the rolling
+/// percentile windows so `/metrics` and `pipeline_stats()` never diverge.
+pub fn record_stage_timing(stage: &str, latency_ms: u64) -> PipelineMetrics {
+    let metrics = check_stage_timing(stage, latency_ms);
+    if let Ok(mut reg) = registry().lock() {
+        reg.stages
+            .entry(metrics.stage_name.clone())
+            .or_default()
+            .observe(latency_ms, metrics.over_budget);
+    }
+    crate::integration::observe_stage_window(&metrics.stage_name, latency_ms, metrics.over_budget);
+    metrics
+}
+
+/// Record the most recent total-pipeline latency gauge.
+pub fn record_total_latency(latency_ms: u64) {
+    if let Ok(mut reg) = registry().lock() {
+        reg.last_total_latency_ms = latency_ms;
+    }
+}
+
+/// Record the error kinds of a validation result under `source`
+/// (e.g. `"validate_intent"` or `"validate_snapshot"`).
+pub fn record_validation_result(source: &str, result: &ValidationResult) {
+    if result.valid {
+        return;
+    }
+    if let Ok(mut reg) = registry().lock() {
+        for error in &result.errors {
+            let key = format!("{source}:{}", classify_error(error));
+            *reg.validation_failures.entry(key).or_insert(0) += 1;
+        }
+    }
+}
+
+/// Map a free-text validation error to a stable, low-cardinality kind label.
+fn classify_error(error: &str) -> &'static str {
+    if error.contains("action_type") {
+        "action_type"
+    } else if error.contains("requires target") {
+        "missing_target"
+    } else if error.contains("Confidence") {
+        "confidence_range"
+    } else if error.contains("capture_id") {
+        "missing_capture_id"
+    } else if error.contains("timestamp") {
+        "missing_timestamp"
+    } else if error.contains("session_id") {
+        "missing_session_id"
+    } else if error.contains("bounds") {
+        "invalid_bounds"
+    } else {
+        "other"
+    }
+}
+
+/// Render the registry in the Prometheus text exposition format.
+pub fn render_prometheus() -> String {
+    let reg = match registry().lock() {
+        Ok(reg) => reg,
+        Err(_) => return String::new(),
+    };
+
+    let mut out = String::new();
+
+    out.push_str("# HELP stage_latency_ms Pipeline stage latency in milliseconds.\n");
+    out.push_str("# TYPE stage_latency_ms histogram\n");
+    let mut stages: Vec<_> = reg.stages.iter().collect();
+    stages.sort_by(|a, b| a.0.cmp(b.0));
+    for (stage, hist) in &stages {
+        for (i, &bound) in BUCKETS.iter().enumerate() {
+            out.push_str(&format!(
+                "stage_latency_ms_bucket{{stage=\"{stage}\",le=\"{bound}\"}} {}\n",
+                hist.buckets[i]
+            ));
+        }
+        out.push_str(&format!(
+            "stage_latency_ms_bucket{{stage=\"{stage}\",le=\"+Inf\"}} {}\n",
+            hist.buckets[BUCKETS.len()]
+        ));
+        out.push_str(&format!(
+            "stage_latency_ms_sum{{stage=\"{stage}\"}} {}\n",
+            hist.sum_ms
+        ));
+        out.push_str(&format!(
+            "stage_latency_ms_count{{stage=\"{stage}\"}} {}\n",
+            hist.count
+        ));
+    }
+
+    out.push_str("# HELP stage_over_budget_total Times a stage exceeded its budget.\n");
+    out.push_str("# TYPE stage_over_budget_total counter\n");
+    for (stage, hist) in &stages {
+        out.push_str(&format!(
+            "stage_over_budget_total{{stage=\"{stage}\"}} {}\n",
+            hist.over_budget
+        ));
+    }
+
+    out.push_str("# HELP pipeline_total_latency_ms Most recent total-pipeline latency.\n");
+    out.push_str("# TYPE pipeline_total_latency_ms gauge\n");
+    out.push_str(&format!(
+        "pipeline_total_latency_ms {}\n",
+        reg.last_total_latency_ms
+    ));
+    out.push_str("# HELP pipeline_total_budget_ms Configured total-pipeline budget.\n");
+    out.push_str("# TYPE pipeline_total_budget_ms gauge\n");
+    out.push_str(&format!("pipeline_total_budget_ms {TOTAL_BUDGET_MS}\n"));
+
+    out.push_str("# HELP validation_failures_total Validation failures by source and kind.\n");
+    out.push_str("# TYPE validation_failures_total counter\n");
+    let mut failures: Vec<_> = reg.validation_failures.iter().collect();
+    failures.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, count) in failures {
+        let (source, kind) = key.split_once(':').unwrap_or((key.as_str(), "other"));
+        out.push_str(&format!(
+            "validation_failures_total{{source=\"{source}\",kind=\"{kind}\"}} {count}\n"
+        ));
+    }
+
+    out
+}
+
+/// Serve `render_prometheus()` over a minimal blocking HTTP `/metrics`
+/// endpoint. Intended to be run on a dedicated thread by the caller.
+pub fn serve(addr: &str) -> std::io::Result<()> {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind(addr)?;
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let mut buf = [0u8; 1024];
+        let _ = stream.read(&mut buf);
+
+        let body = render_prometheus();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let _ = stream.write_all(response.as_bytes());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_observe() {
+        let mut hist = StageHist::default();
+        hist.observe(30, false);
+        // 30ms lands in the <=50, <=100, ... buckets but not <=10 or <=25.
+        assert_eq!(hist.buckets[0], 0); // le=10
+        assert_eq!(hist.buckets[1], 0); // le=25
+        assert_eq!(hist.buckets[2], 1); // le=50
+        assert_eq!(hist.buckets[7], 1); // le=+Inf
+        assert_eq!(hist.count, 1);
+        assert_eq!(hist.sum_ms, 30);
+    }
+
+    #[test]
+    fn test_render_contains_stage() {
+        record_stage_timing("decision", 250);
+        let text = render_prometheus();
+        assert!(text.contains("stage_latency_ms_bucket{stage=\"decision\",le=\"200\"}"));
+        assert!(text.contains("stage_over_budget_total{stage=\"decision\"}"));
+    }
+}
@@ -0,0 +1,234 @@
+//! Data-driven, plane-aware region inference.
+//!
+//! Replaces the hand-coded ladder of rectangular bounds with a [`RegionMap`]
+//! loaded from an external JSON definition. Each region carries one or more
+//! axis-aligned rectangles or polygons, an optional plane constraint and a
+//! priority used to break ties where boxes overlap. A coarse uniform grid
+//! index buckets regions by their bounding-box cells so lookups stay
+//! O(1)-ish even with thousands of map areas.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// World-unit side length of a spatial-index cell.
+const CELL: i32 = 64;
+
+/// Embedded default map, matching the legacy hand-coded bounds. Higher
+/// priorities reproduce the old first-match ordering where boxes are close.
+const DEFAULT_MAP_JSON: &str = r#"[
+    {"name": "Tutorial Island",   "priority": 80, "rects": [{"x1": 3050, "y1": 3050, "x2": 3150, "y2": 3150}]},
+    {"name": "Lumbridge",         "priority": 70, "rects": [{"x1": 3200, "y1": 3200, "x2": 3250, "y2": 3250}]},
+    {"name": "Varrock",           "priority": 60, "rects": [{"x1": 3180, "y1": 3380, "x2": 3290, "y2": 3500}]},
+    {"name": "Falador",           "priority": 50, "rects": [{"x1": 2940, "y1": 3310, "x2": 3040, "y2": 3400}]},
+    {"name": "Draynor",           "priority": 40, "rects": [{"x1": 3080, "y1": 3230, "x2": 3120, "y2": 3280}]},
+    {"name": "Al Kharid",         "priority": 30, "rects": [{"x1": 3270, "y1": 3140, "x2": 3330, "y2": 3200}]},
+    {"name": "Edgeville",         "priority": 20, "rects": [{"x1": 3080, "y1": 3480, "x2": 3110, "y2": 3520}]},
+    {"name": "Barbarian Village", "priority": 10, "rects": [{"x1": 3070, "y1": 3410, "x2": 3110, "y2": 3440}]}
+]"#;
+
+/// An axis-aligned rectangle in world coordinates (inclusive bounds).
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rect {
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
+}
+
+/// A named region: a union of rectangles and/or polygons, optionally
+/// constrained to a single plane, with a tie-break priority.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Region {
+    pub name: String,
+    #[serde(default)]
+    pub rects: Vec<Rect>,
+    /// Polygons as lists of `[x, y]` vertices.
+    #[serde(default)]
+    pub polygons: Vec<Vec<[i32; 2]>>,
+    #[serde(default)]
+    pub plane: Option<i32>,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+impl Region {
+    /// Bounding box `(min_x, min_y, max_x, max_y)` over all shapes.
+    fn bounds(&self) -> Option<(i32, i32, i32, i32)> {
+        let mut bounds: Option<(i32, i32, i32, i32)> = None;
+        let mut extend = |x: i32, y: i32| {
+            bounds = Some(match bounds {
+                None => (x, y, x, y),
+                Some((lx, ly, hx, hy)) => (lx.min(x), ly.min(y), hx.max(x), hy.max(y)),
+            });
+        };
+        for r in &self.rects {
+            extend(r.x1.min(r.x2), r.y1.min(r.y2));
+            extend(r.x1.max(r.x2), r.y1.max(r.y2));
+        }
+        for poly in &self.polygons {
+            for v in poly {
+                extend(v[0], v[1]);
+            }
+        }
+        bounds
+    }
+
+    /// Whether `(x, y)` falls inside any of the region's shapes.
+    fn contains(&self, x: i32, y: i32) -> bool {
+        for r in &self.rects {
+            if x >= r.x1.min(r.x2)
+                && x <= r.x1.max(r.x2)
+                && y >= r.y1.min(r.y2)
+                && y <= r.y1.max(r.y2)
+            {
+                return true;
+            }
+        }
+        self.polygons.iter().any(|p| point_in_polygon(x, y, p))
+    }
+}
+
+/// Even-odd crossing test for a point against a polygon.
+fn point_in_polygon(x: i32, y: i32, poly: &[[i32; 2]]) -> bool {
+    let (px, py) = (x as f64, y as f64);
+    let mut inside = false;
+    let n = poly.len();
+    if n < 3 {
+        return false;
+    }
+    let mut j = n - 1;
+    for i in 0..n {
+        let (xi, yi) = (poly[i][0] as f64, poly[i][1] as f64);
+        let (xj, yj) = (poly[j][0] as f64, poly[j][1] as f64);
+        let intersects = (yi > py) != (yj > py)
+            && px < (xj - xi) * (py - yi) / (yj - yi) + xi;
+        if intersects {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// A loaded set of regions with a coarse uniform grid index.
+pub struct RegionMap {
+    regions: Vec<Region>,
+    /// Cell `(cx, cy)` -> indices of regions whose bounding box overlaps it.
+    index: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl RegionMap {
+    /// Build a map (and its spatial index) from a list of regions.
+    pub fn new(regions: Vec<Region>) -> Self {
+        let mut index: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+        for (i, region) in regions.iter().enumerate() {
+            if let Some((lx, ly, hx, hy)) = region.bounds() {
+                for cx in (lx.div_euclid(CELL))..=(hx.div_euclid(CELL)) {
+                    for cy in (ly.div_euclid(CELL))..=(hy.div_euclid(CELL)) {
+                        index.entry((cx, cy)).or_default().push(i);
+                    }
+                }
+            }
+        }
+        Self { regions, index }
+    }
+
+    /// Parse a map from a JSON array of regions.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let regions: Vec<Region> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        Ok(Self::new(regions))
+    }
+
+    /// Load a map from a JSON definition file on disk.
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::from_json(&json)
+    }
+
+    /// Find the highest-priority region containing `(x, y)`, honoring an
+    /// optional plane constraint. Ties break by priority, then definition
+    /// order.
+    pub fn lookup(&self, x: i32, y: i32, plane: Option<i32>) -> &str {
+        let cell = (x.div_euclid(CELL), y.div_euclid(CELL));
+        let mut best: Option<(i32, usize)> = None;
+        if let Some(candidates) = self.index.get(&cell) {
+            for &i in candidates {
+                let region = &self.regions[i];
+                if let (Some(rp), Some(p)) = (region.plane, plane) {
+                    if rp != p {
+                        continue;
+                    }
+                }
+                if region.contains(x, y) {
+                    let key = (region.priority, i);
+                    let better = match best {
+                        None => true,
+                        Some((bp, bi)) => key.0 > bp || (key.0 == bp && i < bi),
+                    };
+                    if better {
+                        best = Some(key);
+                    }
+                }
+            }
+        }
+        best.map(|(_, i)| self.regions[i].name.as_str())
+            .unwrap_or("unknown")
+    }
+}
+
+/// The lazily-loaded default region map.
+fn default_map() -> &'static RegionMap {
+    use std::sync::OnceLock;
+    static MAP: OnceLock<RegionMap> = OnceLock::new();
+    MAP.get_or_init(|| {
+        RegionMap::from_json(DEFAULT_MAP_JSON).expect("embedded default region map is valid")
+    })
+}
+
+/// Infer the region name for `(x, y)`, ignoring plane (legacy signature).
+pub fn infer_region(x: i32, y: i32) -> &'static str {
+    default_map().lookup(x, y, None)
+}
+
+/// Infer the region name for `(x, y)` on a specific `plane`.
+pub fn infer_region_on_plane(x: i32, y: i32, plane: i32) -> &'static str {
+    default_map().lookup(x, y, Some(plane))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_map_matches_legacy() {
+        assert_eq!(infer_region(3100, 3100), "Tutorial Island");
+        assert_eq!(infer_region(3222, 3218), "Lumbridge");
+        assert_eq!(infer_region(3200, 3400), "Varrock");
+        assert_eq!(infer_region(0, 0), "unknown");
+    }
+
+    #[test]
+    fn test_polygon_and_plane() {
+        let json = r#"[
+            {"name": "Triangle", "priority": 5, "plane": 0,
+             "polygons": [[[0,0],[10,0],[0,10]]]},
+            {"name": "Upstairs", "priority": 5, "plane": 1,
+             "rects": [{"x1": 0, "y1": 0, "x2": 10, "y2": 10}]}
+        ]"#;
+        let map = RegionMap::from_json(json).unwrap();
+        assert_eq!(map.lookup(1, 1, Some(0)), "Triangle");
+        assert_eq!(map.lookup(1, 1, Some(1)), "Upstairs");
+        // Point outside the triangle's lower-left half on plane 0.
+        assert_eq!(map.lookup(9, 9, Some(0)), "unknown");
+    }
+
+    #[test]
+    fn test_priority_tiebreak() {
+        let json = r#"[
+            {"name": "Low",  "priority": 1, "rects": [{"x1": 0, "y1": 0, "x2": 10, "y2": 10}]},
+            {"name": "High", "priority": 9, "rects": [{"x1": 0, "y1": 0, "x2": 10, "y2": 10}]}
+        ]"#;
+        let map = RegionMap::from_json(json).unwrap();
+        assert_eq!(map.lookup(5, 5, None), "High");
+    }
+}
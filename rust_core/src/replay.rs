@@ -0,0 +1,158 @@
+//! PNG capture recording and golden-image replay harness.
+//!
+//! Captures can be logged to disk as PNG and fed back through the detectors
+//! off-line, turning the single in-memory unit test into a growable,
+//! corpus-driven regression suite.
+
+use std::path::Path;
+
+/// Encode an RGBA `buffer` to a PNG file at `path`.
+///
+/// The capture buffers use straight (non-premultiplied) alpha, which is what
+/// PNG stores, so the bytes round-trip through [`load_capture_png`] unchanged
+/// with no premultiply/unpremultiply conversion required.
+pub fn save_capture_png(buffer: &[u8], width: u32, height: u32, path: &Path) -> Result<(), String> {
+    use std::fs::File;
+    use std::io::BufWriter;
+
+    let expected = width as usize * height as usize * 4;
+    if buffer.len() < expected {
+        return Err(format!(
+            "buffer too small: {} bytes for {}x{} RGBA",
+            buffer.len(),
+            width,
+            height
+        ));
+    }
+
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let writer = BufWriter::new(file);
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer
+        .write_image_data(&buffer[..expected])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Load a PNG fixture back into a raw RGBA buffer plus its dimensions.
+///
+/// RGB sources are expanded to RGBA with an opaque alpha channel so the
+/// detectors can consume the result directly.
+pub fn load_capture_png(path: &Path) -> Result<(Vec<u8>, u32, u32), String> {
+    use std::fs::File;
+
+    let decoder = png::Decoder::new(File::open(path).map_err(|e| e.to_string())?);
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buf = vec![0u8; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+    buf.truncate(info.buffer_size());
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => buf,
+        png::ColorType::Rgb => buf
+            .chunks_exact(3)
+            .flat_map(|px| [px[0], px[1], px[2], 255])
+            .collect(),
+        other => return Err(format!("unsupported PNG color type: {:?}", other)),
+    };
+
+    Ok((rgba, info.width, info.height))
+}
+
+// =============================================================================
+// GOLDEN-IMAGE REFTEST HARNESS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detection::{find_cyan_highlight, find_yellow_arrow, DetectionConfig};
+    use crate::types::{DetectionResult, Point};
+
+    /// Tolerances for golden-image comparison.
+    const CENTROID_TOLERANCE: i32 = 5;
+    const CONFIDENCE_TOLERANCE: f32 = 0.1;
+
+    fn assert_point_matches(label: &str, expected: &Option<Point>, actual: &Option<Point>) {
+        match (expected, actual) {
+            (None, None) => {}
+            (Some(e), Some(a)) => {
+                assert!(
+                    (e.x - a.x).abs() <= CENTROID_TOLERANCE
+                        && (e.y - a.y).abs() <= CENTROID_TOLERANCE,
+                    "{label} centroid {:?} outside tolerance of {:?}",
+                    (a.x, a.y),
+                    (e.x, e.y)
+                );
+                assert!(
+                    (e.confidence - a.confidence).abs() <= CONFIDENCE_TOLERANCE,
+                    "{label} confidence {} outside tolerance of {}",
+                    a.confidence,
+                    e.confidence
+                );
+            }
+            (e, a) => panic!("{label} presence mismatch: expected {e:?}, got {a:?}"),
+        }
+    }
+
+    /// Replay every `*.png` fixture paired with a `*.expected.json`
+    /// [`DetectionResult`] and assert the detectors stay within tolerance.
+    /// The corpus lives under `tests/fixtures`; an empty or missing directory
+    /// is a no-op so the suite passes until fixtures are recorded.
+    #[test]
+    fn test_golden_replay() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        let config = DetectionConfig::default();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("png") {
+                continue;
+            }
+            let expected_path = path.with_extension("expected.json");
+            let expected_json = match std::fs::read_to_string(&expected_path) {
+                Ok(json) => json,
+                Err(_) => continue,
+            };
+            let expected: DetectionResult =
+                serde_json::from_str(&expected_json).expect("valid expected JSON");
+
+            let (img, w, h) = load_capture_png(&path).expect("loadable PNG fixture");
+            let arrow = find_yellow_arrow(&img, w, h, &config)
+                .map(|(x, y, confidence)| Point { x, y, confidence });
+            let highlight = find_cyan_highlight(&img, w, h, &config)
+                .map(|(x, y, confidence)| Point { x, y, confidence });
+
+            let label = path.file_name().unwrap().to_string_lossy().to_string();
+            assert_point_matches(&format!("{label} arrow"), &expected.arrow, &arrow);
+            assert_point_matches(&format!("{label} highlight"), &expected.highlight, &highlight);
+        }
+    }
+
+    #[test]
+    fn test_png_roundtrip() {
+        let mut img = vec![0u8; 16 * 16 * 4];
+        for (i, px) in img.chunks_exact_mut(4).enumerate() {
+            px[0] = (i % 256) as u8;
+            px[1] = 128;
+            px[2] = 64;
+            px[3] = 255;
+        }
+
+        let dir = std::env::temp_dir();
+        let path = dir.join("osrs_core_roundtrip.png");
+        save_capture_png(&img, 16, 16, &path).expect("save");
+        let (loaded, w, h) = load_capture_png(&path).expect("load");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!((w, h), (16, 16));
+        assert_eq!(loaded, img);
+    }
+}
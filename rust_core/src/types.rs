@@ -8,6 +8,32 @@ pub struct DetectionResult {
     pub highlight: Option<Point>,
     pub capture_ms: u64,
     pub detect_ms: u64,
+    /// True when detection was skipped and a cached result was returned
+    /// because the scene did not change since the previous frame.
+    #[serde(default)]
+    pub reused: bool,
+    /// Mean luma (0..255) of the captured ROI, used to drive auto-gain.
+    #[serde(default)]
+    pub mean_luma: f32,
+    /// Brightness gain applied to the value thresholds this frame.
+    #[serde(default = "default_gain")]
+    pub gain: f32,
+    /// Bounding boxes from the optional neural object detector, if it ran.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub objects: Option<Vec<Detection>>,
+}
+
+/// A single object-detection box from the neural backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Detection {
+    pub class: String,
+    /// Bounding box as `(x, y, width, height)` in ROI pixel coordinates.
+    pub bbox: (i32, i32, i32, i32),
+    pub confidence: f32,
+}
+
+fn default_gain() -> f32 {
+    1.0
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +50,10 @@ impl DetectionResult {
             highlight: None,
             capture_ms: 0,
             detect_ms: 0,
+            reused: false,
+            mean_luma: 0.0,
+            gain: 1.0,
+            objects: None,
         }
     }
 }